@@ -0,0 +1,33 @@
+use crate::memory::Mem;
+
+/* A real 6502 does not know anything about "memory": it drives a 16-bit
+ * address bus and an 8-bit data bus, and whatever is wired to a given address
+ * responds. That can be RAM, a ROM region, or a memory-mapped peripheral such
+ * as a timer, a display controller or a keyboard register. Modelling the bus
+ * as a trait lets a caller decide what lives behind each address instead of
+ * hard-wiring the CPU to a flat array.
+ */
+
+/// Abstraction over the 6502 address/data bus.
+///
+/// Implementors map the 16-bit address space to whatever they like — plain
+/// RAM, mirrored regions, or memory-mapped devices. The CPU only ever touches
+/// the outside world through `read`/`write`.
+pub trait Bus {
+    /// Read the byte currently presented at `addr`.
+    fn read(&self, addr: u16) -> u8;
+
+    /// Write `value` to `addr`.
+    fn write(&mut self, addr: u16, value: u8);
+}
+
+/// The flat 64KB memory behaves as a bus that maps every address to RAM.
+impl Bus for Mem {
+    fn read(&self, addr: u16) -> u8 {
+        self[addr as usize]
+    }
+
+    fn write(&mut self, addr: u16, value: u8) {
+        self[addr as usize] = value;
+    }
+}
@@ -1,4 +1,80 @@
-use crate::memory::Mem;
+use crate::bus::Bus;
+
+/// Instruction-set variant emulated by the [`CPU`].
+///
+/// The original NMOS 6502 and the later WDC 65C02 (CMOS) share a common core;
+/// the CMOS part adds a handful of new opcodes and addressing modes and tidies
+/// up a few corner cases (for example it clears the decimal flag when an
+/// interrupt is taken). The variant is fixed at construction time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// Original NMOS 6502.
+    Nmos,
+    /// WDC 65C02 (CMOS) with the extended instruction set.
+    Cmos,
+}
+
+/// How an instruction forms the effective address of its operand.
+///
+/// Each variant corresponds to one of the 6502 addressing modes. `Implied` and
+/// `Accumulator` carry no memory operand; `Relative` is used by branches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressingMode {
+    Immediate,
+    ZeroPage,
+    ZeroPageX,
+    ZeroPageY,
+    Absolute,
+    AbsoluteX,
+    AbsoluteY,
+    /// `(zp,X)` — indexed indirect.
+    IndexedIndirect,
+    /// `(zp),Y` — indirect indexed.
+    IndirectIndexed,
+    Accumulator,
+    Implied,
+    Relative,
+}
+
+/// A decoded instruction mnemonic, independent of its addressing mode.
+///
+/// Only the families routed through the decode engine are listed here; the
+/// remaining opcodes (JSR, stack, flag and interrupt operations and the 65C02
+/// extensions) are still handled as dedicated arms in [`CPU::dispatch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Lda,
+    Ldx,
+    Ldy,
+    Sta,
+    Stx,
+    Sty,
+    Tax,
+    Txa,
+    Tay,
+    Tya,
+    Tsx,
+    Txs,
+    Asl,
+    Lsr,
+    Rol,
+    Ror,
+    Inc,
+    Dec,
+}
+
+/// Result of running a self-checking test ROM to completion.
+///
+/// Conformance ROMs report their verdict by trapping in a tight self-loop: a
+/// known "success" address means every check passed, any other address is the
+/// check that failed. [`CPU::run_test`] turns that trap address into this.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestOutcome {
+    /// The ROM reached its success trap.
+    Success,
+    /// The ROM trapped elsewhere; the payload is the offending address.
+    Failure(u16),
+}
 
 /// 6502 CPU emulator.
 pub struct CPU {
@@ -30,7 +106,13 @@ pub struct CPU {
    * Retrieved from: https://en.wikipedia.org/wiki/Stack_register
    */ 
 
-    sp: u16, // Stack pointer
+    /* On the 6502 the stack pointer is an 8-bit register. The stack itself
+   * lives in page 1, so the effective address of the top of stack is
+   * 0x0100 | sp, wrapping inside 0x0100..=0x01FF. We store only the offset
+   * here and form the full address in `stack_addr`.
+   */
+
+    sp: u8, // Stack pointer (offset into page 0x01)
     
     /* A processor register is a quickly accesible location available to a 
    * computer processor. Registers usually consit of a small amount of fast
@@ -64,13 +146,26 @@ pub struct CPU {
    * Retrieved from: 
    * */ 
 
-    c: u8, // Carry flag
-    z: u8, // Zero flag  
-    i: u8, // Interrupt Disable 
-    d: u8, // Decimal Mode
-    b: u8, // Break Command
-    v: u8, // Overflow flag
-    n: u8, // Negative flag
+    /* The seven condition flags are not stored as independent bytes anymore.
+   * A real 6502 keeps them packed in a single 8-bit processor status register
+   * (P), which is what PHP/PLP push and pull and what the interrupt sequence
+   * saves on the stack. The canonical bit layout is:
+   *
+   *     bit 7  6  5  4  3  2  1  0
+   *            N  V  1  B  D  I  Z  C
+   *
+   * bit 5 is unused and always reads as 1. We store the packed byte here and
+   * derive the individual getters from it so existing callers keep working.
+   */
+
+    p: u8, // Processor status register
+
+    /* Which instruction set this core emulates. The NMOS/CMOS distinction is
+   * consulted in `execute` so that the 65C02-only opcodes fall through to the
+   * "instruction not handled" path when running as a plain 6502.
+   */
+
+    variant: Variant,
 }
 
 impl CPU {
@@ -80,30 +175,223 @@ impl CPU {
     /// LDA inmediate
     pub const INS_LDA_IM: u8 = 0xA9;
     /// LDA zero page
-    pub const INS_LDA_ZP: u8 = 0xA5; 
+    pub const INS_LDA_ZP: u8 = 0xA5;
     /// LDA zero page X
     pub const INS_LDA_ZPX: u8 = 0xB5;
     /// JSR (junp to subroutine)
-    pub const INS_JSR: u8 = 0x20; 
+    pub const INS_JSR: u8 = 0x20;
+
+    // LDA — load accumulator.
+    pub const INS_LDA_ABS: u8 = 0xAD;
+    pub const INS_LDA_ABSX: u8 = 0xBD;
+    pub const INS_LDA_ABSY: u8 = 0xB9;
+    pub const INS_LDA_INDX: u8 = 0xA1;
+    pub const INS_LDA_INDY: u8 = 0xB1;
+
+    // LDX — load X register.
+    pub const INS_LDX_IM: u8 = 0xA2;
+    pub const INS_LDX_ZP: u8 = 0xA6;
+    pub const INS_LDX_ZPY: u8 = 0xB6;
+    pub const INS_LDX_ABS: u8 = 0xAE;
+    pub const INS_LDX_ABSY: u8 = 0xBE;
+
+    // LDY — load Y register.
+    pub const INS_LDY_IM: u8 = 0xA0;
+    pub const INS_LDY_ZP: u8 = 0xA4;
+    pub const INS_LDY_ZPX: u8 = 0xB4;
+    pub const INS_LDY_ABS: u8 = 0xAC;
+    pub const INS_LDY_ABSX: u8 = 0xBC;
+
+    // STA — store accumulator.
+    pub const INS_STA_ZP: u8 = 0x85;
+    pub const INS_STA_ZPX: u8 = 0x95;
+    pub const INS_STA_ABS: u8 = 0x8D;
+    pub const INS_STA_ABSX: u8 = 0x9D;
+    pub const INS_STA_ABSY: u8 = 0x99;
+    pub const INS_STA_INDX: u8 = 0x81;
+    pub const INS_STA_INDY: u8 = 0x91;
+
+    // STX — store X register.
+    pub const INS_STX_ZP: u8 = 0x86;
+    pub const INS_STX_ZPY: u8 = 0x96;
+    pub const INS_STX_ABS: u8 = 0x8E;
+
+    // STY — store Y register.
+    pub const INS_STY_ZP: u8 = 0x84;
+    pub const INS_STY_ZPX: u8 = 0x94;
+    pub const INS_STY_ABS: u8 = 0x8C;
+
+    // Register transfers (implied).
+    pub const INS_TAX: u8 = 0xAA;
+    pub const INS_TXA: u8 = 0x8A;
+    pub const INS_TAY: u8 = 0xA8;
+    pub const INS_TYA: u8 = 0x98;
+    pub const INS_TSX: u8 = 0xBA;
+    pub const INS_TXS: u8 = 0x9A;
+
+    // ASL — arithmetic shift left.
+    pub const INS_ASL_A: u8 = 0x0A;
+    pub const INS_ASL_ZP: u8 = 0x06;
+    pub const INS_ASL_ZPX: u8 = 0x16;
+    pub const INS_ASL_ABS: u8 = 0x0E;
+    pub const INS_ASL_ABSX: u8 = 0x1E;
+
+    // LSR — logical shift right.
+    pub const INS_LSR_A: u8 = 0x4A;
+    pub const INS_LSR_ZP: u8 = 0x46;
+    pub const INS_LSR_ZPX: u8 = 0x56;
+    pub const INS_LSR_ABS: u8 = 0x4E;
+    pub const INS_LSR_ABSX: u8 = 0x5E;
+
+    // ROL — rotate left.
+    pub const INS_ROL_A: u8 = 0x2A;
+    pub const INS_ROL_ZP: u8 = 0x26;
+    pub const INS_ROL_ZPX: u8 = 0x36;
+    pub const INS_ROL_ABS: u8 = 0x2E;
+    pub const INS_ROL_ABSX: u8 = 0x3E;
+
+    // ROR — rotate right.
+    pub const INS_ROR_A: u8 = 0x6A;
+    pub const INS_ROR_ZP: u8 = 0x66;
+    pub const INS_ROR_ZPX: u8 = 0x76;
+    pub const INS_ROR_ABS: u8 = 0x6E;
+    pub const INS_ROR_ABSX: u8 = 0x7E;
+
+    // INC — increment memory.
+    pub const INS_INC_ZP: u8 = 0xE6;
+    pub const INS_INC_ZPX: u8 = 0xF6;
+    pub const INS_INC_ABS: u8 = 0xEE;
+    pub const INS_INC_ABSX: u8 = 0xFE;
+
+    // DEC — decrement memory.
+    pub const INS_DEC_ZP: u8 = 0xC6;
+    pub const INS_DEC_ZPX: u8 = 0xD6;
+    pub const INS_DEC_ABS: u8 = 0xCE;
+    pub const INS_DEC_ABSX: u8 = 0xDE;
+
+    /// BRK (force interrupt).
+    pub const INS_BRK: u8 = 0x00;
+    /// RTI (return from interrupt).
+    pub const INS_RTI: u8 = 0x40;
+
+    // Hardware interrupt and reset vectors (little-endian pointers).
+
+    /// Non-maskable interrupt vector.
+    pub const VECTOR_NMI: u16 = 0xFFFA;
+    /// Reset vector.
+    pub const VECTOR_RESET: u16 = 0xFFFC;
+    /// IRQ/BRK vector.
+    pub const VECTOR_IRQ: u16 = 0xFFFE;
+
+    // WDC 65C02 (CMOS) extensions. These are only decoded in `Variant::Cmos`.
+
+    /// BRA (branch always, relative).
+    pub const INS_BRA: u8 = 0x80;
+    /// STZ zero page.
+    pub const INS_STZ_ZP: u8 = 0x64;
+    /// STZ zero page X.
+    pub const INS_STZ_ZPX: u8 = 0x74;
+    /// STZ absolute.
+    pub const INS_STZ_ABS: u8 = 0x9C;
+    /// STZ absolute X.
+    pub const INS_STZ_ABSX: u8 = 0x9E;
+    /// PHX (push X).
+    pub const INS_PHX: u8 = 0xDA;
+    /// PHY (push Y).
+    pub const INS_PHY: u8 = 0x5A;
+    /// PLX (pull X).
+    pub const INS_PLX: u8 = 0xFA;
+    /// PLY (pull Y).
+    pub const INS_PLY: u8 = 0x7A;
+    /// INC A (increment accumulator).
+    pub const INS_INC_A: u8 = 0x1A;
+    /// DEC A (decrement accumulator).
+    pub const INS_DEC_A: u8 = 0x3A;
+    /// TSB zero page (test and set bits).
+    pub const INS_TSB_ZP: u8 = 0x04;
+    /// TSB absolute.
+    pub const INS_TSB_ABS: u8 = 0x0C;
+    /// TRB zero page (test and reset bits).
+    pub const INS_TRB_ZP: u8 = 0x14;
+    /// TRB absolute.
+    pub const INS_TRB_ABS: u8 = 0x1C;
+    /// BIT immediate.
+    pub const INS_BIT_IM: u8 = 0x89;
+    /// LDA (zp) zero-page indirect.
+    pub const INS_LDA_IND_ZP: u8 = 0xB2;
+    /// STA (zp) zero-page indirect.
+    pub const INS_STA_IND_ZP: u8 = 0x92;
+
+    /// PHP (push processor status).
+    pub const INS_PHP: u8 = 0x08;
+    /// PLP (pull processor status).
+    pub const INS_PLP: u8 = 0x28;
+    /// PHA (push accumulator).
+    pub const INS_PHA: u8 = 0x48;
+    /// PLA (pull accumulator).
+    pub const INS_PLA: u8 = 0x68;
+    /// SEC (set carry flag).
+    pub const INS_SEC: u8 = 0x38;
+    /// CLC (clear carry flag).
+    pub const INS_CLC: u8 = 0x18;
+    /// SEI (set interrupt disable).
+    pub const INS_SEI: u8 = 0x78;
+    /// CLI (clear interrupt disable).
+    pub const INS_CLI: u8 = 0x58;
+    /// SED (set decimal mode).
+    pub const INS_SED: u8 = 0xF8;
+    /// CLD (clear decimal mode).
+    pub const INS_CLD: u8 = 0xD8;
+    /// CLV (clear overflow flag).
+    pub const INS_CLV: u8 = 0xB8;
+
+    // Processor status flag masks (canonical 6502 bit layout).
+
+    /// Carry (bit 0).
+    pub const FLAG_C: u8 = 1 << 0;
+    /// Zero (bit 1).
+    pub const FLAG_Z: u8 = 1 << 1;
+    /// Interrupt disable (bit 2).
+    pub const FLAG_I: u8 = 1 << 2;
+    /// Decimal mode (bit 3).
+    pub const FLAG_D: u8 = 1 << 3;
+    /// Break command (bit 4).
+    pub const FLAG_B: u8 = 1 << 4;
+    /// Unused (bit 5), always reads as 1.
+    pub const FLAG_U: u8 = 1 << 5;
+    /// Overflow (bit 6).
+    pub const FLAG_V: u8 = 1 << 6;
+    /// Negative (bit 7).
+    pub const FLAG_N: u8 = 1 << 7;
 
     /// Create a new CPU Instance with registers cleared.
     pub fn new() -> Self {
+        Self::with_variant(Variant::Nmos)
+    }
+
+    /// Create a new CMOS (WDC 65C02) CPU with the extended instruction set.
+    pub fn new_cmos() -> Self {
+        Self::with_variant(Variant::Cmos)
+    }
+
+    /// Create a new CPU for the given instruction-set variant, registers cleared.
+    pub fn with_variant(variant: Variant) -> Self {
         Self {
             pc: 0,
             sp: 0,
             a: 0,
             x: 0,
             y: 0,
-            c: 0,
-            z: 0,
-            i: 0,
-            d: 0,
-            b: 0,
-            v: 0,
-            n: 0,
+            p: Self::FLAG_U,
+            variant,
         }
     }
 
+    /// Whether this core decodes the 65C02 (CMOS) extensions.
+    pub fn is_cmos(&self) -> bool {
+        self.variant == Variant::Cmos
+    }
+
     /* Reset refers to the process of returning the computer to 
     * the apparent default (or ground) state of the computer – with or 
     * without memory intact. The computer will return to the default start-up 
@@ -144,109 +432,777 @@ impl CPU {
 
     /// Reset the CPU to its initial state.
     ///
-    /// This method sets the program counter to the reset vector (0xFFFC), initializes the stack
-    /// pointer, clears registers and flags, and initializes memory.
-    pub fn reset(&mut self, memory: &mut Mem) {
-        self.pc = 0xFFFC;
-        self.sp = 0x0100;
+    /// On real hardware 0xFFFC/0xFFFD is the *reset vector*: the CPU reads those
+    /// two bytes (little-endian) and jumps to the address they contain. The
+    /// stack pointer is left at 0xFD, the interrupt-disable flag is set and the
+    /// other registers are cleared. RAM is not touched by a reset.
+    pub fn reset<B: Bus>(&mut self, bus: &mut B) {
         self.a = 0;
         self.x = 0;
         self.y = 0;
-        self.c = 0;
-        self.z = 0;
-        self.i = 1;
-        self.d = 0;
-        self.b = 0;
-        self.v = 0;
-        self.n = 0;  
-        memory.initialize();
+        self.sp = 0xFD;
+        self.p = Self::FLAG_U | Self::FLAG_I;
+        self.pc = self.read_vector(Self::VECTOR_RESET, bus);
     }
 
-    /// Fetch one byte from memory at the current PC.
+    /// Read a little-endian 16-bit pointer from a fixed vector address.
+    fn read_vector<B: Bus>(&self, address: u16, bus: &B) -> u16 {
+        let low = bus.read(address) as u16;
+        let high = bus.read(address.wrapping_add(1)) as u16;
+        (high << 8) | low
+    }
+
+    /// Fetch one byte from the bus at the current PC.
     /// This decrements the cycle count by 1.
-    pub fn fetch_byte(&mut self, cycles: &mut u32, memory: &Mem) -> u8 {
-        let data = memory[self.pc as usize];
+    pub fn fetch_byte<B: Bus>(&mut self, cycles: &mut u32, bus: &B) -> u8 {
+        let data = bus.read(self.pc);
         self.pc = self.pc.wrapping_add(1);
         *cycles = cycles.saturating_sub(1);
         data
     }
 
-    /// Fetch a 16-bit word (2 bytes) from memory in little-endian order.
+    /// Fetch a 16-bit word (2 bytes) from the bus in little-endian order.
     /// This decrements the cycle count by 2.
-    pub fn fetch_word(&mut self, cycles: &mut u32, memory: &Mem) -> u16 {
+    pub fn fetch_word<B: Bus>(&mut self, cycles: &mut u32, bus: &B) -> u16 {
         // 6502 is little-endian: first byte is the least significant.
-        let low = memory[self.pc as usize] as u16;
+        let low = bus.read(self.pc) as u16;
         self.pc = self.pc.wrapping_add(1);
-        let high = memory[self.pc as usize] as u16;
+        let high = bus.read(self.pc) as u16;
         self.pc = self.pc.wrapping_add(1);
         *cycles = cycles.saturating_sub(2);
         (high << 8) | low
     }
 
-    /// Read one byte from memory given an 8-bit address.
+    /// Read one byte from the bus given an 8-bit (zero-page) address.
+    /// This decrements the cycle count by 1.
+    pub fn read_byte<B: Bus>(&mut self, cycles: &mut u32, address: u8, bus: &B) -> u8 {
+        let data = bus.read(address as u16);
+        *cycles = cycles.saturating_sub(1);
+        data
+    }
+
+    /// Return the packed processor status register (P).
+    ///
+    /// Bit 5 is unused on real silicon and always reads as 1, so it is forced
+    /// here regardless of how the flags were assembled.
+    pub fn status(&self) -> u8 {
+        self.p | Self::FLAG_U
+    }
+
+    /// Replace the whole processor status register from a packed byte.
+    ///
+    /// Bit 5 is forced to 1 to match hardware behaviour.
+    pub fn set_status(&mut self, value: u8) {
+        self.p = value | Self::FLAG_U;
+    }
+
+    /// Set or clear an individual flag inside the packed status register.
+    fn set_flag(&mut self, flag: u8, on: bool) {
+        if on {
+            self.p |= flag;
+        } else {
+            self.p &= !flag;
+        }
+    }
+
+    /// Effective address of the current top of stack in page 0x01.
+    fn stack_addr(&self) -> u16 {
+        0x0100 | self.sp as u16
+    }
+
+    /// Push a single byte onto the stack (page 0x01) and decrement the pointer.
+    /// This decrements the cycle count by 1.
+    fn push_byte<B: Bus>(&mut self, value: u8, cycles: &mut u32, bus: &mut B) {
+        bus.write(self.stack_addr(), value);
+        self.sp = self.sp.wrapping_sub(1);
+        *cycles = cycles.saturating_sub(1);
+    }
+
+    /// Pull a single byte off the stack (page 0x01) and increment the pointer.
     /// This decrements the cycle count by 1.
-    pub fn read_byte(&mut self, cycles: &mut u32, address: u8, memory: &Mem) -> u8 {
-        let data = memory[address as usize];
+    fn pull_byte<B: Bus>(&mut self, cycles: &mut u32, bus: &B) -> u8 {
+        self.sp = self.sp.wrapping_add(1);
+        let data = bus.read(self.stack_addr());
         *cycles = cycles.saturating_sub(1);
         data
     }
 
+    /// Push a 16-bit value onto the stack, high byte first (little-endian on
+    /// the descending stack). This decrements the cycle count by 2.
+    fn push_word<B: Bus>(&mut self, value: u16, cycles: &mut u32, bus: &mut B) {
+        self.push_byte((value >> 8) as u8, cycles, bus);
+        self.push_byte((value & 0xFF) as u8, cycles, bus);
+    }
+
+    /// Pull a 16-bit value off the stack, low byte first.
+    /// This decrements the cycle count by 2.
+    fn pull_word<B: Bus>(&mut self, cycles: &mut u32, bus: &B) -> u16 {
+        let low = self.pull_byte(cycles, bus) as u16;
+        let high = self.pull_byte(cycles, bus) as u16;
+        (high << 8) | low
+    }
+
+    /// Common body of the hardware interrupt sequence: push the return address
+    /// and the status register, set the interrupt-disable flag and jump through
+    /// the given vector. `brk` selects whether the pushed copy of P has the B
+    /// flag set (software BRK) or clear (hardware IRQ/NMI).
+    fn interrupt<B: Bus>(&mut self, vector: u16, brk: bool, cycles: &mut u32, bus: &mut B) {
+        self.push_word(self.pc, cycles, bus);
+        let mut status = self.status();
+        if brk {
+            status |= Self::FLAG_B;
+        } else {
+            status &= !Self::FLAG_B;
+        }
+        self.push_byte(status, cycles, bus);
+        self.set_flag(Self::FLAG_I, true);
+        // The 65C02 leaves the core in a known state by clearing the decimal
+        // flag when an interrupt is taken; the NMOS part does not.
+        if self.is_cmos() {
+            self.set_flag(Self::FLAG_D, false);
+        }
+        self.pc = self.read_vector(vector, bus);
+        // Fetching the two-byte vector costs two cycles.
+        *cycles = cycles.saturating_sub(2);
+    }
+
+    /// Service a maskable interrupt request, returning the number of cycles
+    /// consumed.
+    ///
+    /// The request is ignored (and 0 cycles are consumed) while the
+    /// interrupt-disable flag is set; otherwise the sequence takes 7 cycles.
+    pub fn irq<B: Bus>(&mut self, bus: &mut B) -> u32 {
+        if self.get_i() != 0 {
+            return 0;
+        }
+        self.take_interrupt(Self::VECTOR_IRQ, bus)
+    }
+
+    /// Service a non-maskable interrupt, returning the number of cycles
+    /// consumed (always 7).
+    pub fn nmi<B: Bus>(&mut self, bus: &mut B) -> u32 {
+        self.take_interrupt(Self::VECTOR_NMI, bus)
+    }
+
+    /// Run the hardware interrupt sequence through the given vector and report
+    /// the cycles consumed: push(3) + vector(2) plus two internal cycles.
+    fn take_interrupt<B: Bus>(&mut self, vector: u16, bus: &mut B) -> u32 {
+        let mut cycles = u32::MAX;
+        self.interrupt(vector, false, &mut cycles, bus);
+        cycles = cycles.saturating_sub(2);
+        u32::MAX - cycles
+    }
+
     /// Set the status flags based on the contents of the accumulator.
     pub fn lda_set_status(&mut self) {
-        self.z = if self.a == 0 {1} else {0}; 
-        self.n = if (self.a & 0b10000000) > 0 {1} else {0};
+        self.set_flag(Self::FLAG_Z, self.a == 0);
+        self.set_flag(Self::FLAG_N, (self.a & 0b1000_0000) != 0);
+    }
+
+    /// Update the zero and negative flags from an arbitrary result byte.
+    fn set_zn(&mut self, value: u8) {
+        self.set_flag(Self::FLAG_Z, value == 0);
+        self.set_flag(Self::FLAG_N, (value & 0b1000_0000) != 0);
+    }
+
+    /// Apply a signed relative branch offset to the program counter, charging
+    /// one extra cycle and a second one when the branch crosses a page.
+    fn branch_relative(&mut self, offset: u8, cycles: &mut u32) {
+        let old = self.pc;
+        self.pc = self.pc.wrapping_add(offset as i8 as u16);
+        *cycles = cycles.saturating_sub(1);
+        if (old & 0xFF00) != (self.pc & 0xFF00) {
+            *cycles = cycles.saturating_sub(1);
+        }
+    }
+
+    /// Resolve the 65C02 zero-page-indirect `(zp)` addressing mode: the
+    /// zero-page operand points at a little-endian pointer, itself in page 0.
+    fn addr_zp_indirect<B: Bus>(&mut self, cycles: &mut u32, bus: &B) -> u16 {
+        let zp = self.fetch_byte(cycles, bus);
+        let low = bus.read(zp as u16) as u16;
+        let high = bus.read(zp.wrapping_add(1) as u16) as u16;
+        *cycles = cycles.saturating_sub(2);
+        (high << 8) | low
+    }
+
+    /// Resolve the effective address of an operand for the given addressing
+    /// mode, charging the cycles needed to compute it (operand fetch plus any
+    /// indexing) but not the final data access.
+    ///
+    /// The returned flag indicates whether indexing crossed a page boundary; a
+    /// load pays one extra cycle only in that case, while a store always pays
+    /// it. `Accumulator`, `Implied` and `Relative` have no memory operand and
+    /// return address 0.
+    fn resolve_address<B: Bus>(
+        &mut self,
+        mode: AddressingMode,
+        cycles: &mut u32,
+        bus: &B,
+    ) -> (u16, bool) {
+        match mode {
+            AddressingMode::Immediate => {
+                // The operand byte is read by the instruction itself, so no
+                // cycle is charged here.
+                let addr = self.pc;
+                self.pc = self.pc.wrapping_add(1);
+                (addr, false)
+            }
+            AddressingMode::ZeroPage => {
+                let addr = self.fetch_byte(cycles, bus) as u16;
+                (addr, false)
+            }
+            AddressingMode::ZeroPageX => {
+                let zp = self.fetch_byte(cycles, bus);
+                *cycles = cycles.saturating_sub(1);
+                (zp.wrapping_add(self.x) as u16, false)
+            }
+            AddressingMode::ZeroPageY => {
+                let zp = self.fetch_byte(cycles, bus);
+                *cycles = cycles.saturating_sub(1);
+                (zp.wrapping_add(self.y) as u16, false)
+            }
+            AddressingMode::Absolute => {
+                let addr = self.fetch_word(cycles, bus);
+                (addr, false)
+            }
+            AddressingMode::AbsoluteX => {
+                let base = self.fetch_word(cycles, bus);
+                let addr = base.wrapping_add(self.x as u16);
+                (addr, (base & 0xFF00) != (addr & 0xFF00))
+            }
+            AddressingMode::AbsoluteY => {
+                let base = self.fetch_word(cycles, bus);
+                let addr = base.wrapping_add(self.y as u16);
+                (addr, (base & 0xFF00) != (addr & 0xFF00))
+            }
+            AddressingMode::IndexedIndirect => {
+                let zp = self.fetch_byte(cycles, bus);
+                let ptr = zp.wrapping_add(self.x);
+                *cycles = cycles.saturating_sub(1);
+                let low = bus.read(ptr as u16) as u16;
+                let high = bus.read(ptr.wrapping_add(1) as u16) as u16;
+                *cycles = cycles.saturating_sub(2);
+                ((high << 8) | low, false)
+            }
+            AddressingMode::IndirectIndexed => {
+                let zp = self.fetch_byte(cycles, bus);
+                let low = bus.read(zp as u16) as u16;
+                let high = bus.read(zp.wrapping_add(1) as u16) as u16;
+                *cycles = cycles.saturating_sub(2);
+                let base = (high << 8) | low;
+                let addr = base.wrapping_add(self.y as u16);
+                (addr, (base & 0xFF00) != (addr & 0xFF00))
+            }
+            AddressingMode::Accumulator
+            | AddressingMode::Implied
+            | AddressingMode::Relative => (0, false),
+        }
+    }
+
+    /// Map an opcode byte to an (instruction, addressing mode) pair for the
+    /// families routed through the decode engine, or `None` when it is handled
+    /// elsewhere.
+    fn decode(ins: u8) -> Option<(Instruction, AddressingMode)> {
+        use AddressingMode::*;
+        use Instruction::*;
+        let pair = match ins {
+            Self::INS_LDA_IM => (Lda, Immediate),
+            Self::INS_LDA_ZP => (Lda, ZeroPage),
+            Self::INS_LDA_ZPX => (Lda, ZeroPageX),
+            Self::INS_LDA_ABS => (Lda, Absolute),
+            Self::INS_LDA_ABSX => (Lda, AbsoluteX),
+            Self::INS_LDA_ABSY => (Lda, AbsoluteY),
+            Self::INS_LDA_INDX => (Lda, IndexedIndirect),
+            Self::INS_LDA_INDY => (Lda, IndirectIndexed),
+
+            Self::INS_LDX_IM => (Ldx, Immediate),
+            Self::INS_LDX_ZP => (Ldx, ZeroPage),
+            Self::INS_LDX_ZPY => (Ldx, ZeroPageY),
+            Self::INS_LDX_ABS => (Ldx, Absolute),
+            Self::INS_LDX_ABSY => (Ldx, AbsoluteY),
+
+            Self::INS_LDY_IM => (Ldy, Immediate),
+            Self::INS_LDY_ZP => (Ldy, ZeroPage),
+            Self::INS_LDY_ZPX => (Ldy, ZeroPageX),
+            Self::INS_LDY_ABS => (Ldy, Absolute),
+            Self::INS_LDY_ABSX => (Ldy, AbsoluteX),
+
+            Self::INS_STA_ZP => (Sta, ZeroPage),
+            Self::INS_STA_ZPX => (Sta, ZeroPageX),
+            Self::INS_STA_ABS => (Sta, Absolute),
+            Self::INS_STA_ABSX => (Sta, AbsoluteX),
+            Self::INS_STA_ABSY => (Sta, AbsoluteY),
+            Self::INS_STA_INDX => (Sta, IndexedIndirect),
+            Self::INS_STA_INDY => (Sta, IndirectIndexed),
+
+            Self::INS_STX_ZP => (Stx, ZeroPage),
+            Self::INS_STX_ZPY => (Stx, ZeroPageY),
+            Self::INS_STX_ABS => (Stx, Absolute),
+
+            Self::INS_STY_ZP => (Sty, ZeroPage),
+            Self::INS_STY_ZPX => (Sty, ZeroPageX),
+            Self::INS_STY_ABS => (Sty, Absolute),
+
+            Self::INS_TAX => (Tax, Implied),
+            Self::INS_TXA => (Txa, Implied),
+            Self::INS_TAY => (Tay, Implied),
+            Self::INS_TYA => (Tya, Implied),
+            Self::INS_TSX => (Tsx, Implied),
+            Self::INS_TXS => (Txs, Implied),
+
+            Self::INS_ASL_A => (Asl, Accumulator),
+            Self::INS_ASL_ZP => (Asl, ZeroPage),
+            Self::INS_ASL_ZPX => (Asl, ZeroPageX),
+            Self::INS_ASL_ABS => (Asl, Absolute),
+            Self::INS_ASL_ABSX => (Asl, AbsoluteX),
+
+            Self::INS_LSR_A => (Lsr, Accumulator),
+            Self::INS_LSR_ZP => (Lsr, ZeroPage),
+            Self::INS_LSR_ZPX => (Lsr, ZeroPageX),
+            Self::INS_LSR_ABS => (Lsr, Absolute),
+            Self::INS_LSR_ABSX => (Lsr, AbsoluteX),
+
+            Self::INS_ROL_A => (Rol, Accumulator),
+            Self::INS_ROL_ZP => (Rol, ZeroPage),
+            Self::INS_ROL_ZPX => (Rol, ZeroPageX),
+            Self::INS_ROL_ABS => (Rol, Absolute),
+            Self::INS_ROL_ABSX => (Rol, AbsoluteX),
+
+            Self::INS_ROR_A => (Ror, Accumulator),
+            Self::INS_ROR_ZP => (Ror, ZeroPage),
+            Self::INS_ROR_ZPX => (Ror, ZeroPageX),
+            Self::INS_ROR_ABS => (Ror, Absolute),
+            Self::INS_ROR_ABSX => (Ror, AbsoluteX),
+
+            Self::INS_INC_ZP => (Inc, ZeroPage),
+            Self::INS_INC_ZPX => (Inc, ZeroPageX),
+            Self::INS_INC_ABS => (Inc, Absolute),
+            Self::INS_INC_ABSX => (Inc, AbsoluteX),
+
+            Self::INS_DEC_ZP => (Dec, ZeroPage),
+            Self::INS_DEC_ZPX => (Dec, ZeroPageX),
+            Self::INS_DEC_ABS => (Dec, Absolute),
+            Self::INS_DEC_ABSX => (Dec, AbsoluteX),
+
+            _ => return None,
+        };
+        Some(pair)
+    }
+
+    /// Carry out a decoded load/store/transfer or read-modify-write instruction.
+    fn execute_decoded<B: Bus>(
+        &mut self,
+        instruction: Instruction,
+        mode: AddressingMode,
+        cycles: &mut u32,
+        bus: &mut B,
+    ) {
+        use Instruction::*;
+        match instruction {
+            Lda | Ldx | Ldy => {
+                let (addr, crossed) = self.resolve_address(mode, cycles, bus);
+                let value = bus.read(addr);
+                *cycles = cycles.saturating_sub(1);
+                if crossed {
+                    *cycles = cycles.saturating_sub(1);
+                }
+                match instruction {
+                    Lda => self.a = value,
+                    Ldx => self.x = value,
+                    _ => self.y = value,
+                }
+                self.set_zn(value);
+            }
+            Sta | Stx | Sty => {
+                let (addr, _crossed) = self.resolve_address(mode, cycles, bus);
+                let value = match instruction {
+                    Sta => self.a,
+                    Stx => self.x,
+                    _ => self.y,
+                };
+                bus.write(addr, value);
+                *cycles = cycles.saturating_sub(1);
+                // Stores always pay the indexing penalty regardless of whether
+                // a page boundary was crossed.
+                if matches!(
+                    mode,
+                    AddressingMode::AbsoluteX
+                        | AddressingMode::AbsoluteY
+                        | AddressingMode::IndirectIndexed
+                ) {
+                    *cycles = cycles.saturating_sub(1);
+                }
+            }
+            Tax => {
+                self.x = self.a;
+                self.set_zn(self.x);
+                *cycles = cycles.saturating_sub(1);
+            }
+            Txa => {
+                self.a = self.x;
+                self.set_zn(self.a);
+                *cycles = cycles.saturating_sub(1);
+            }
+            Tay => {
+                self.y = self.a;
+                self.set_zn(self.y);
+                *cycles = cycles.saturating_sub(1);
+            }
+            Tya => {
+                self.a = self.y;
+                self.set_zn(self.a);
+                *cycles = cycles.saturating_sub(1);
+            }
+            Tsx => {
+                self.x = self.sp;
+                self.set_zn(self.x);
+                *cycles = cycles.saturating_sub(1);
+            }
+            Txs => {
+                // TXS does not affect any flags.
+                self.sp = self.x;
+                *cycles = cycles.saturating_sub(1);
+            }
+            Asl | Lsr | Rol | Ror | Inc | Dec => {
+                if mode == AddressingMode::Accumulator {
+                    self.a = self.rmw_op(instruction, self.a);
+                    *cycles = cycles.saturating_sub(1);
+                } else {
+                    let (addr, _crossed) = self.resolve_address(mode, cycles, bus);
+                    // Indexed RMW always spends the extra index cycle.
+                    if mode == AddressingMode::AbsoluteX {
+                        *cycles = cycles.saturating_sub(1);
+                    }
+                    let old = bus.read(addr);
+                    *cycles = cycles.saturating_sub(1);
+                    let new = self.rmw_op(instruction, old);
+                    // The dummy write of the original value is observable on
+                    // real hardware, so it must go through the bus.
+                    bus.write(addr, old);
+                    *cycles = cycles.saturating_sub(1);
+                    bus.write(addr, new);
+                    *cycles = cycles.saturating_sub(1);
+                }
+            }
+        }
+    }
+
+    /// Apply a shift/rotate or increment/decrement operation to a byte,
+    /// updating N/Z (and the carry for shifts and rotates), and return the
+    /// result.
+    fn rmw_op(&mut self, instruction: Instruction, value: u8) -> u8 {
+        use Instruction::*;
+        let carry_in = self.get_c();
+        let result = match instruction {
+            Asl => {
+                self.set_flag(Self::FLAG_C, (value & 0x80) != 0);
+                value << 1
+            }
+            Lsr => {
+                self.set_flag(Self::FLAG_C, (value & 0x01) != 0);
+                value >> 1
+            }
+            Rol => {
+                self.set_flag(Self::FLAG_C, (value & 0x80) != 0);
+                (value << 1) | carry_in
+            }
+            Ror => {
+                self.set_flag(Self::FLAG_C, (value & 0x01) != 0);
+                (value >> 1) | (carry_in << 7)
+            }
+            Inc => value.wrapping_add(1),
+            Dec => value.wrapping_sub(1),
+            _ => value,
+        };
+        self.set_zn(result);
+        result
     }
 
     /// Execute instructions until the cycle count reaches zero.
     ///
     /// This method has a loop that fetches an opcoed and uses a `match` to determine which
     /// operation to perform.
-    pub fn execute(&mut self, mut cycles: u32, memory: &mut Mem) {
+    ///
+    /// Returns `None` when the cycle budget is exhausted, or `Some(opcode)` with
+    /// the offending byte when an opcode is not handled, so the caller can react
+    /// instead of relying on a side-effecting print.
+    pub fn execute<B: Bus>(&mut self, mut cycles: u32, memory: &mut B) -> Option<u8> {
         while cycles > 0 {
             let ins: u8 = self.fetch_byte(&mut cycles, memory);
-            match ins {
-                Self::INS_LDA_IM => {
-                    let value = self.fetch_byte(&mut cycles, memory);
-                    self.a = value;
-                    self.lda_set_status();
-                }
-                Self::INS_LDA_ZP => {
-                    let zp_addr = self.fetch_byte(&mut cycles, memory);
-                    self.a = self.read_byte(&mut cycles, zp_addr, memory);
-                    self.lda_set_status();
-                }
+            if !self.dispatch(ins, &mut cycles, memory) {
+                return Some(ins);
+            }
+        }
+        None
+    }
+
+    /// Execute a single already-fetched opcode, returning `true` if it was
+    /// decoded. `cycles` is decremented by the operation's cost.
+    fn dispatch<B: Bus>(&mut self, ins: u8, cycles_ref: &mut u32, memory: &mut B) -> bool {
+        let mut cycles = *cycles_ref;
+        // The load/store/transfer and read-modify-write families go through
+        // the addressing-mode decode engine; everything else is a dedicated
+        // arm below.
+        if let Some((instruction, mode)) = Self::decode(ins) {
+            self.execute_decoded(instruction, mode, &mut cycles, memory);
+            *cycles_ref = cycles;
+            return true;
+        }
+        match ins {
                 Self::INS_JSR => {
                     let sub_addr: u16 = self.fetch_word(&mut cycles, memory);
-                    // Write return address (PC - 1) to the stack.
-                    memory.write_word(self.pc.wrapping_sub(1), self.sp as u32, &mut cycles);
+                    // Push the return address (PC - 1) onto the stack.
+                    self.push_word(self.pc.wrapping_sub(1), &mut cycles, memory);
                     self.pc = sub_addr;
-                    self.sp = self.sp.wrapping_add(2);
                     cycles = cycles.saturating_sub(1);
                 }
-                _ => {
-                    println!("Instruction not handled {:#X}", ins);
-                    break;
+                Self::INS_BRK => {
+                    // BRK is a 1-byte opcode but pushes PC+1 so the signature
+                    // byte is skipped on return. P is pushed with the B flag set
+                    // and the whole sequence consumes 7 cycles.
+                    self.pc = self.pc.wrapping_add(1);
+                    self.interrupt(Self::VECTOR_IRQ, true, &mut cycles, memory);
+                    // One internal cycle on top of fetch(1) + push(3) + vector(2).
+                    cycles = cycles.saturating_sub(1);
+                }
+                Self::INS_RTI => {
+                    let status = self.pull_byte(&mut cycles, memory);
+                    self.set_status(status);
+                    self.pc = self.pull_word(&mut cycles, memory);
+                    cycles = cycles.saturating_sub(2);
+                }
+                Self::INS_PHP => {
+                    // PHP pushes P with the B flag (and bit 5) set.
+                    let value = self.status() | Self::FLAG_B;
+                    self.push_byte(value, &mut cycles, memory);
+                    cycles = cycles.saturating_sub(1);
+                }
+                Self::INS_PLP => {
+                    let value = self.pull_byte(&mut cycles, memory);
+                    self.set_status(value);
+                    cycles = cycles.saturating_sub(2);
+                }
+                Self::INS_PHA => {
+                    self.push_byte(self.a, &mut cycles, memory);
+                    cycles = cycles.saturating_sub(1);
+                }
+                Self::INS_PLA => {
+                    self.a = self.pull_byte(&mut cycles, memory);
+                    self.lda_set_status();
+                    cycles = cycles.saturating_sub(2);
+                }
+                Self::INS_SEC => {
+                    self.set_flag(Self::FLAG_C, true);
+                    cycles = cycles.saturating_sub(1);
+                }
+                Self::INS_CLC => {
+                    self.set_flag(Self::FLAG_C, false);
+                    cycles = cycles.saturating_sub(1);
+                }
+                Self::INS_SEI => {
+                    self.set_flag(Self::FLAG_I, true);
+                    cycles = cycles.saturating_sub(1);
+                }
+                Self::INS_CLI => {
+                    self.set_flag(Self::FLAG_I, false);
+                    cycles = cycles.saturating_sub(1);
+                }
+                Self::INS_SED => {
+                    self.set_flag(Self::FLAG_D, true);
+                    cycles = cycles.saturating_sub(1);
+                }
+                Self::INS_CLD => {
+                    self.set_flag(Self::FLAG_D, false);
+                    cycles = cycles.saturating_sub(1);
+                }
+                Self::INS_CLV => {
+                    self.set_flag(Self::FLAG_V, false);
+                    cycles = cycles.saturating_sub(1);
+                }
+                Self::INS_BRA if self.is_cmos() => {
+                    let offset = self.fetch_byte(&mut cycles, memory);
+                    self.branch_relative(offset, &mut cycles);
+                }
+                Self::INS_STZ_ZP if self.is_cmos() => {
+                    let addr = self.fetch_byte(&mut cycles, memory) as u16;
+                    memory.write(addr, 0);
+                    cycles = cycles.saturating_sub(1);
+                }
+                Self::INS_STZ_ZPX if self.is_cmos() => {
+                    let addr = self.fetch_byte(&mut cycles, memory).wrapping_add(self.x) as u16;
+                    memory.write(addr, 0);
+                    cycles = cycles.saturating_sub(2);
+                }
+                Self::INS_STZ_ABS if self.is_cmos() => {
+                    let addr = self.fetch_word(&mut cycles, memory);
+                    memory.write(addr, 0);
+                    cycles = cycles.saturating_sub(1);
+                }
+                Self::INS_STZ_ABSX if self.is_cmos() => {
+                    let addr = self.fetch_word(&mut cycles, memory).wrapping_add(self.x as u16);
+                    memory.write(addr, 0);
+                    cycles = cycles.saturating_sub(2);
+                }
+                Self::INS_PHX if self.is_cmos() => {
+                    self.push_byte(self.x, &mut cycles, memory);
+                    cycles = cycles.saturating_sub(1);
+                }
+                Self::INS_PHY if self.is_cmos() => {
+                    self.push_byte(self.y, &mut cycles, memory);
+                    cycles = cycles.saturating_sub(1);
+                }
+                Self::INS_PLX if self.is_cmos() => {
+                    self.x = self.pull_byte(&mut cycles, memory);
+                    self.set_zn(self.x);
+                    cycles = cycles.saturating_sub(2);
+                }
+                Self::INS_PLY if self.is_cmos() => {
+                    self.y = self.pull_byte(&mut cycles, memory);
+                    self.set_zn(self.y);
+                    cycles = cycles.saturating_sub(2);
                 }
+                Self::INS_INC_A if self.is_cmos() => {
+                    self.a = self.a.wrapping_add(1);
+                    self.set_zn(self.a);
+                    cycles = cycles.saturating_sub(1);
+                }
+                Self::INS_DEC_A if self.is_cmos() => {
+                    self.a = self.a.wrapping_sub(1);
+                    self.set_zn(self.a);
+                    cycles = cycles.saturating_sub(1);
+                }
+                Self::INS_TSB_ZP if self.is_cmos() => {
+                    let addr = self.fetch_byte(&mut cycles, memory) as u16;
+                    let value = memory.read(addr);
+                    self.set_flag(Self::FLAG_Z, (value & self.a) == 0);
+                    memory.write(addr, value | self.a);
+                    cycles = cycles.saturating_sub(3);
+                }
+                Self::INS_TSB_ABS if self.is_cmos() => {
+                    let addr = self.fetch_word(&mut cycles, memory);
+                    let value = memory.read(addr);
+                    self.set_flag(Self::FLAG_Z, (value & self.a) == 0);
+                    memory.write(addr, value | self.a);
+                    cycles = cycles.saturating_sub(3);
+                }
+                Self::INS_TRB_ZP if self.is_cmos() => {
+                    let addr = self.fetch_byte(&mut cycles, memory) as u16;
+                    let value = memory.read(addr);
+                    self.set_flag(Self::FLAG_Z, (value & self.a) == 0);
+                    memory.write(addr, value & !self.a);
+                    cycles = cycles.saturating_sub(3);
+                }
+                Self::INS_TRB_ABS if self.is_cmos() => {
+                    let addr = self.fetch_word(&mut cycles, memory);
+                    let value = memory.read(addr);
+                    self.set_flag(Self::FLAG_Z, (value & self.a) == 0);
+                    memory.write(addr, value & !self.a);
+                    cycles = cycles.saturating_sub(3);
+                }
+                Self::INS_BIT_IM if self.is_cmos() => {
+                    let value = self.fetch_byte(&mut cycles, memory);
+                    // The immediate form only affects the zero flag.
+                    self.set_flag(Self::FLAG_Z, (self.a & value) == 0);
+                }
+                Self::INS_LDA_IND_ZP if self.is_cmos() => {
+                    let addr = self.addr_zp_indirect(&mut cycles, memory);
+                    self.a = memory.read(addr);
+                    cycles = cycles.saturating_sub(1);
+                    self.lda_set_status();
+                }
+                Self::INS_STA_IND_ZP if self.is_cmos() => {
+                    let addr = self.addr_zp_indirect(&mut cycles, memory);
+                    memory.write(addr, self.a);
+                    cycles = cycles.saturating_sub(1);
+                }
+                _ => return false,
+            }
+        *cycles_ref = cycles;
+        true
+    }
+
+    /// Execute exactly one instruction, returning the number of cycles it
+    /// consumed. This is the per-instruction stepping primitive that lets
+    /// integration tests drive conformance ROMs one opcode at a time.
+    ///
+    /// An unrecognised opcode consumes only its fetch cycle and leaves the
+    /// rest of the CPU state untouched.
+    pub fn step<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        let mut cycles = u32::MAX;
+        let ins = self.fetch_byte(&mut cycles, memory);
+        self.dispatch(ins, &mut cycles, memory);
+        u32::MAX - cycles
+    }
+
+    /// Run the CPU until it reaches a tight self-loop (an instruction that
+    /// leaves the program counter unchanged, such as the `JMP *` trap the
+    /// 6502 functional test uses to report a result).
+    ///
+    /// Returns the program counter of the detected loop. A guard on the total
+    /// number of steps prevents a runaway ROM from spinning forever.
+    ///
+    /// See [`CPU::run_test`] for turning the trap address into a pass/fail
+    /// verdict. Klaus Dormann's full `6502_functional_test.bin` is not yet
+    /// driveable end-to-end — it uses JMP, the NMOS conditional branches,
+    /// ADC/SBC, CMP/CPX/CPY and the logical ops, which this core does not decode
+    /// yet, so it trips the unhandled-opcode path before its success trap — but
+    /// any ROM built from the implemented instruction set runs to completion.
+    pub fn run_until_loop<B: Bus>(&mut self, memory: &mut B, max_steps: u64) -> u16 {
+        let mut steps = 0;
+        loop {
+            let before = self.pc;
+            self.step(memory);
+            steps += 1;
+            if self.pc == before || steps >= max_steps {
+                return self.pc;
             }
         }
     }
+
+    /// Run a self-checking test ROM to its trap and report the verdict.
+    ///
+    /// The ROM is expected to spin in a tight self-loop once it finishes. If
+    /// that loop is at `success_addr` the run is a [`TestOutcome::Success`];
+    /// otherwise the trap address is returned as [`TestOutcome::Failure`]. The
+    /// `max_steps` guard is forwarded to [`CPU::run_until_loop`].
+    pub fn run_test<B: Bus>(
+        &mut self,
+        memory: &mut B,
+        success_addr: u16,
+        max_steps: u64,
+    ) -> TestOutcome {
+        let pc = self.run_until_loop(memory, max_steps);
+        if pc == success_addr {
+            TestOutcome::Success
+        } else {
+            TestOutcome::Failure(pc)
+        }
+    }
 }
 
 
+impl Default for CPU {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CPU {
     // Getters
     pub fn get_pc(&self) -> u16 { self.pc }
-    pub fn get_sp(&self) -> u16 { self.sp }
+    pub fn get_sp(&self) -> u16 { self.sp as u16 }
     pub fn get_a(&self) -> u8 { self.a }
     pub fn get_x(&self) -> u8 { self.x }
     pub fn get_y(&self) -> u8 { self.y }
-    pub fn get_c(&self) -> u8 { self.c }
-    pub fn get_z(&self) -> u8 { self.z }
-    pub fn get_i(&self) -> u8 { self.i }
-    pub fn get_d(&self) -> u8 { self.d }
-    pub fn get_b(&self) -> u8 { self.b }
-    pub fn get_v(&self) -> u8 { self.v }
-    pub fn get_n(&self) -> u8 { self.n }
+    // Individual flags are derived from the packed status register.
+    pub fn get_c(&self) -> u8 { (self.p & Self::FLAG_C != 0) as u8 }
+    pub fn get_z(&self) -> u8 { (self.p & Self::FLAG_Z != 0) as u8 }
+    pub fn get_i(&self) -> u8 { (self.p & Self::FLAG_I != 0) as u8 }
+    pub fn get_d(&self) -> u8 { (self.p & Self::FLAG_D != 0) as u8 }
+    pub fn get_b(&self) -> u8 { (self.p & Self::FLAG_B != 0) as u8 }
+    pub fn get_v(&self) -> u8 { (self.p & Self::FLAG_V != 0) as u8 }
+    pub fn get_n(&self) -> u8 { (self.p & Self::FLAG_N != 0) as u8 }
 
     // Setters
     pub fn set_pc(&mut self, value: u16) { self.pc = value; }
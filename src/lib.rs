@@ -12,4 +12,5 @@
  */ 
 
 pub mod memory;
+pub mod bus;
 pub mod cpu;
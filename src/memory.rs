@@ -27,6 +27,17 @@ impl Mem {
         self.data.fill(0);
     }
 
+    /// Load a block of bytes into memory starting at `offset`.
+    ///
+    /// This is used to place a ROM image (for example a functional-test binary)
+    /// at its origin before running. Bytes that would run past the end of the
+    /// 64KB address space are ignored.
+    pub fn load(&mut self, offset: u16, bytes: &[u8]) {
+        let start = offset as usize;
+        let end = (start + bytes.len()).min(MAX_MEM);
+        self.data[start..end].copy_from_slice(&bytes[..end - start]);
+    }
+
     /// Write a 16-bit word (2 bytes) in little-endian order to memory.
     /// This method also decrements the cycle count by 2.
     pub fn write_word(&mut self, value: u16, address: u32, cycles: &mut u32) {
@@ -36,6 +47,12 @@ impl Mem {
     }
 }
 
+impl Default for Mem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Allow read-only indexing into the memory.
 impl Index<usize> for Mem {
     type Output = u8;
@@ -1,6 +1,7 @@
 // m6502rs/tests/integration_test.rs
 
-use m6502rs::cpu::CPU;
+use m6502rs::bus::Bus;
+use m6502rs::cpu::{TestOutcome, CPU};
 use m6502rs::memory::{Mem, MAX_MEM};
 
 #[cfg(test)]
@@ -29,22 +30,17 @@ mod tests {
     fn test_cpu_reset() {
         let mut cpu = CPU::new();
         let mut mem = Mem::new();
-        // Para diferenciar, llenamos la memoria con un valor distinto a cero.
-        for i in 0..MAX_MEM {
-            mem[i] = 0xFF;
-        }
+        // El reset lee el vector en 0xFFFC/0xFFFD (little-endian) y salta allí.
+        mem[0xFFFC] = 0x00;
+        mem[0xFFFD] = 0x80;
         cpu.reset(&mut mem);
         // Tras el reset, se deben cumplir estas condiciones:
-        assert_eq!(cpu.get_pc(), 0xFFFC);
-        assert_eq!(cpu.get_sp(), 0x0100);
+        assert_eq!(cpu.get_pc(), 0x8000); // Dirección apuntada por el vector.
+        assert_eq!(cpu.get_sp(), 0xFD);
         assert_eq!(cpu.get_a(), 0);
         assert_eq!(cpu.get_x(), 0);
         assert_eq!(cpu.get_y(), 0);
         assert_eq!(cpu.get_i(), 1); // La bandera de interrupción se pone en 1.
-        // Y la memoria debe haberse inicializado (todos ceros).
-        for i in 0..MAX_MEM {
-            assert_eq!(mem[i], 0);
-        }
     }
 
     #[test]
@@ -146,28 +142,30 @@ mod tests {
     fn test_execute_jsr() {
         let mut cpu = CPU::new();
         let mut mem = Mem::new();
-        // Para probar JSR usamos reset, que establece pc = 0xFFFC y sp = 0x0100.
+        // El vector de reset apunta a 0xFFF0, donde colocamos la instrucción JSR.
+        mem[0xFFFC] = 0xF0;
+        mem[0xFFFD] = 0xFF;
         cpu.reset(&mut mem);
-        // Colocamos la instrucción JSR en la dirección de reset (0xFFFC).
-        mem[0xFFFC] = CPU::INS_JSR;
+        assert_eq!(cpu.get_pc(), 0xFFF0);
+        mem[0xFFF0] = CPU::INS_JSR;
         // Queremos saltar a la dirección 0x1234.
         // Recordar: en little-endian, primero el byte menos significativo.
-        mem[0xFFFD] = 0x34; // Low byte
-        mem[0xFFFE] = 0x12; // High byte
-        let cycles = 15; // Suficientes ciclos para completar la instrucción.
-        cpu.execute(cycles, &mut mem);
+        mem[0xFFF1] = 0x34; // Low byte
+        mem[0xFFF2] = 0x12; // High byte
+        // Ejecutamos exactamente una instrucción (JSR son 6 ciclos); usar un
+        // presupuesto mayor haría que el bucle siguiera ejecutando en 0x1234.
+        cpu.step(&mut mem);
         // Luego de la instrucción, se espera que:
         // - El contador de programa (pc) sea 0x1234.
-        // - El puntero de pila (sp) se haya decrementado en 1: de 0x0100 a 0x00FF.
+        // - El puntero de pila (sp) haya bajado de 0xFD a 0xFB (dos bytes).
         assert_eq!(cpu.get_pc(), 0x1234);
-        assert_eq!(cpu.get_sp(), 0x00FF);
+        assert_eq!(cpu.get_sp(), 0xFB);
         // Además, se debe haber escrito la dirección de retorno (pc - 1) en la pila.
-        // Tras JSR, el pc ya se incrementó a 0xFFFF (después de extraer los dos bytes),
-        // por lo que el valor a guardar es 0xFFFE (0xFFFF - 1) escrito en memoria en 0x0100.
-        // En little-endian: low byte en mem[0x0100] y high byte en mem[0x0101].
-        assert_eq!(mem[0x0100], 0xFE);
-        assert_eq!(mem[0x0101], 0xFF);
-    } 
+        // Tras extraer los dos bytes del operando, pc vale 0xFFF3, así que el valor
+        // guardado es 0xFFF2: high byte en 0x01FD, low byte en 0x01FC.
+        assert_eq!(mem[0x01FD], 0xFF);
+        assert_eq!(mem[0x01FC], 0xF2);
+    }
 
     #[test]
     fn test_mem_initialize() {
@@ -198,6 +196,413 @@ mod tests {
         assert_eq!(cycles, 8);
     }
 
+    #[test]
+    fn test_execute_ldx_immediate() {
+        let mut cpu = CPU::new();
+        let mut mem = Mem::new();
+        cpu.set_pc(0);
+        mem.load(0, &[CPU::INS_LDX_IM, 0x80]);
+        cpu.execute(2, &mut mem);
+        assert_eq!(cpu.get_x(), 0x80);
+        // 0x80 tiene el bit de signo activo.
+        assert_eq!(cpu.get_n(), 1);
+        assert_eq!(cpu.get_z(), 0);
+    }
+
+    #[test]
+    fn test_execute_sta_zero_page() {
+        let mut cpu = CPU::new();
+        let mut mem = Mem::new();
+        cpu.set_pc(0);
+        cpu.set_a(0x42);
+        mem.load(0, &[CPU::INS_STA_ZP, 0x20]);
+        cpu.execute(3, &mut mem);
+        assert_eq!(mem[0x20], 0x42);
+    }
+
+    #[test]
+    fn test_execute_tax() {
+        let mut cpu = CPU::new();
+        let mut mem = Mem::new();
+        cpu.set_pc(0);
+        cpu.set_a(0x10);
+        mem.load(0, &[CPU::INS_TAX]);
+        cpu.execute(2, &mut mem);
+        assert_eq!(cpu.get_x(), 0x10);
+        assert_eq!(cpu.get_z(), 0);
+    }
+
+    #[test]
+    fn test_execute_asl_zero_page() {
+        let mut cpu = CPU::new();
+        let mut mem = Mem::new();
+        cpu.set_pc(0);
+        mem.load(0, &[CPU::INS_ASL_ZP, 0x10]);
+        mem[0x10] = 0x80; // bit 7 activo -> carry tras el desplazamiento.
+        let cycles = cpu.step(&mut mem);
+        assert_eq!(cycles, 5); // ASL zero page consume 5 ciclos.
+        assert_eq!(mem[0x10], 0x00);
+        assert_eq!(cpu.get_c(), 1);
+        assert_eq!(cpu.get_z(), 1);
+    }
+
+    #[test]
+    fn test_execute_inc_zero_page() {
+        let mut cpu = CPU::new();
+        let mut mem = Mem::new();
+        cpu.set_pc(0);
+        mem.load(0, &[CPU::INS_INC_ZP, 0x20]);
+        mem[0x20] = 0x7F;
+        let cycles = cpu.step(&mut mem);
+        assert_eq!(cycles, 5);
+        assert_eq!(mem[0x20], 0x80);
+        // 0x80 tiene el bit de signo activo.
+        assert_eq!(cpu.get_n(), 1);
+        assert_eq!(cpu.get_z(), 0);
+    }
+
+    #[test]
+    fn test_mem_load() {
+        let mut mem = Mem::new();
+        // Cargamos una pequeña imagen en el offset 0x0200.
+        mem.load(0x0200, &[0xA9, 0x01, 0x4C]);
+        assert_eq!(mem[0x0200], 0xA9);
+        assert_eq!(mem[0x0201], 0x01);
+        assert_eq!(mem[0x0202], 0x4C);
+        // El resto de la memoria debe seguir en cero.
+        assert_eq!(mem[0x0203], 0x00);
+    }
+
+    #[test]
+    fn test_step_returns_cycles() {
+        let mut cpu = CPU::new();
+        let mut mem = Mem::new();
+        cpu.set_pc(0);
+        mem.load(0, &[CPU::INS_LDA_IM, 0x55]);
+        // Un LDA inmediato consume 2 ciclos (fetch del opcode + fetch del dato).
+        let cycles = cpu.step(&mut mem);
+        assert_eq!(cycles, 2);
+        assert_eq!(cpu.get_a(), 0x55);
+        assert_eq!(cpu.get_pc(), 2);
+    }
+
+    #[test]
+    fn test_run_until_loop_detects_self_branch() {
+        let mut cpu = CPU::new_cmos();
+        let mut mem = Mem::new();
+        cpu.set_pc(0x0600);
+        // BRA con offset 0xFE salta de vuelta a sí mismo (bucle cerrado).
+        mem.load(0x0600, &[CPU::INS_BRA, 0xFE]);
+        let pc = cpu.run_until_loop(&mut mem, 1000);
+        assert_eq!(pc, 0x0600);
+    }
+
+    #[test]
+    fn test_brk_and_rti() {
+        let mut cpu = CPU::new();
+        let mut mem = Mem::new();
+        // Vector de reset -> 0x0600 (donde ponemos el BRK) y vector IRQ/BRK -> 0x8000.
+        mem[0xFFFC] = 0x00;
+        mem[0xFFFD] = 0x06;
+        mem[0xFFFE] = 0x00;
+        mem[0xFFFF] = 0x80;
+        mem[0x0600] = CPU::INS_BRK;
+        mem[0x8000] = CPU::INS_RTI;
+        cpu.reset(&mut mem);
+        // Activamos el acarreo para comprobar que RTI restaura P más adelante.
+        cpu.set_status(CPU::FLAG_C | CPU::FLAG_I);
+
+        let cycles = cpu.step(&mut mem);
+        assert_eq!(cycles, 7); // BRK consume 7 ciclos.
+        assert_eq!(cpu.get_pc(), 0x8000); // Salto a través del vector 0xFFFE.
+        assert_eq!(cpu.get_i(), 1); // BRK fija la bandera de interrupción.
+        assert_eq!(cpu.get_sp(), 0xFA); // Tres bytes empujados (PC alto/bajo + P).
+        // BRK salta el byte de firma y empuja 0x0602: byte alto en 0x01FD, bajo en 0x01FC.
+        assert_eq!(mem[0x01FD], 0x06);
+        assert_eq!(mem[0x01FC], 0x02);
+        // P se empuja con la bandera B activa: 0x25 | 0x10 = 0x35.
+        assert_eq!(mem[0x01FB], 0x35);
+
+        let cycles = cpu.step(&mut mem);
+        assert_eq!(cycles, 6); // RTI consume 6 ciclos.
+        assert_eq!(cpu.get_pc(), 0x0602); // Dirección de retorno recuperada.
+        assert_eq!(cpu.get_c(), 1); // El acarreo sobrevive al viaje por la pila.
+        assert_eq!(cpu.get_sp(), 0xFD); // La pila vuelve a su punto de partida.
+    }
+
+    #[test]
+    fn test_irq_masked_when_i_set() {
+        let mut cpu = CPU::new();
+        let mut mem = Mem::new();
+        mem[0xFFFC] = 0x00;
+        mem[0xFFFD] = 0x06;
+        cpu.reset(&mut mem); // El reset deja la bandera de interrupción activa.
+        // Con I=1 la IRQ se ignora: 0 ciclos y el PC no se mueve.
+        assert_eq!(cpu.irq(&mut mem), 0);
+        assert_eq!(cpu.get_pc(), 0x0600);
+    }
+
+    #[test]
+    fn test_nmi_ignores_i() {
+        let mut cpu = CPU::new();
+        let mut mem = Mem::new();
+        mem[0xFFFC] = 0x00;
+        mem[0xFFFD] = 0x06;
+        mem[0xFFFA] = 0x00; // Vector NMI -> 0x9000.
+        mem[0xFFFB] = 0x90;
+        cpu.reset(&mut mem);
+        // La NMI no es enmascarable: se atiende aunque I esté activa.
+        assert_eq!(cpu.nmi(&mut mem), 7);
+        assert_eq!(cpu.get_pc(), 0x9000);
+    }
+
+    #[test]
+    fn test_status_round_trip() {
+        let mut cpu = CPU::new();
+        // El bit 5 no existe en silicio y siempre se lee como 1, así que
+        // 0xC3 se reensambla como 0xE3 al leerlo de vuelta.
+        cpu.set_status(0xC3);
+        assert_eq!(cpu.status(), 0xE3);
+        // Cada getter debe reflejar el bit correspondiente del byte empaquetado.
+        assert_eq!(cpu.get_n(), 1);
+        assert_eq!(cpu.get_v(), 1);
+        assert_eq!(cpu.get_z(), 1);
+        assert_eq!(cpu.get_c(), 1);
+        assert_eq!(cpu.get_b(), 0);
+        assert_eq!(cpu.get_d(), 0);
+        assert_eq!(cpu.get_i(), 0);
+    }
+
+    #[test]
+    fn test_php_plp_round_trip() {
+        let mut cpu = CPU::new();
+        let mut mem = Mem::new();
+        // El reset deja el puntero de pila en 0xFD.
+        cpu.reset(&mut mem);
+        cpu.set_pc(0x0600);
+        mem.load(0x0600, &[CPU::INS_PHP, CPU::INS_PLP]);
+        // Partimos de N y C activos (0x81).
+        cpu.set_status(0x81);
+        // PHP empuja P con la bandera B (y el bit 5) activos: 0x81|0x30 = 0xB1.
+        cpu.step(&mut mem);
+        assert_eq!(cpu.get_sp(), 0xFC);
+        assert_eq!(mem[0x01FD], 0xB1);
+        // Ensuciamos el registro para comprobar que PLP lo restaura.
+        cpu.set_status(0x00);
+        cpu.step(&mut mem);
+        assert_eq!(cpu.get_sp(), 0xFD);
+        assert_eq!(cpu.status(), 0xB1);
+    }
+
+    #[test]
+    fn test_pha_pla_round_trip() {
+        let mut cpu = CPU::new();
+        let mut mem = Mem::new();
+        // El reset deja el puntero de pila en 0xFD.
+        cpu.reset(&mut mem);
+        cpu.set_pc(0x0600);
+        mem.load(0x0600, &[CPU::INS_PHA, CPU::INS_PLA]);
+        cpu.set_a(0x37);
+        cpu.step(&mut mem);
+        assert_eq!(cpu.get_sp(), 0xFC);
+        assert_eq!(mem[0x01FD], 0x37);
+        // Borramos el acumulador y lo recuperamos con PLA.
+        cpu.set_a(0x00);
+        cpu.step(&mut mem);
+        assert_eq!(cpu.get_sp(), 0xFD);
+        assert_eq!(cpu.get_a(), 0x37);
+        // 0x37 no es cero ni tiene el bit de signo activo.
+        assert_eq!(cpu.get_z(), 0);
+        assert_eq!(cpu.get_n(), 0);
+    }
+
+    #[test]
+    fn test_flag_ops() {
+        let mut cpu = CPU::new();
+        let mut mem = Mem::new();
+        cpu.set_pc(0x0600);
+        // Partimos con V activa para ver cómo CLV la limpia; el resto arranca en 0.
+        cpu.set_status(CPU::FLAG_V);
+        // Cada "set" seguido de su "clear" ejercita ambas direcciones.
+        mem.load(
+            0x0600,
+            &[
+                CPU::INS_SEC,
+                CPU::INS_CLC,
+                CPU::INS_SEI,
+                CPU::INS_CLI,
+                CPU::INS_SED,
+                CPU::INS_CLD,
+                CPU::INS_CLV,
+            ],
+        );
+        cpu.step(&mut mem);
+        assert_eq!(cpu.get_c(), 1); // SEC
+        cpu.step(&mut mem);
+        assert_eq!(cpu.get_c(), 0); // CLC
+        cpu.step(&mut mem);
+        assert_eq!(cpu.get_i(), 1); // SEI
+        cpu.step(&mut mem);
+        assert_eq!(cpu.get_i(), 0); // CLI
+        cpu.step(&mut mem);
+        assert_eq!(cpu.get_d(), 1); // SED
+        cpu.step(&mut mem);
+        assert_eq!(cpu.get_d(), 0); // CLD
+        cpu.step(&mut mem);
+        assert_eq!(cpu.get_v(), 0); // CLV
+    }
+
+    /// Un bus de ejemplo que mapea 0x4000 a un periférico y el resto a RAM.
+    /// Sirve para comprobar que la CPU solo toca el mundo a través de
+    /// `read`/`write` del trait `Bus`.
+    struct DeviceBus {
+        ram: [u8; 0x10000],
+        dev_in: u8,
+        dev_out: Option<u8>,
+    }
+
+    impl Bus for DeviceBus {
+        fn read(&self, addr: u16) -> u8 {
+            if addr == 0x4000 {
+                self.dev_in
+            } else {
+                self.ram[addr as usize]
+            }
+        }
+
+        fn write(&mut self, addr: u16, value: u8) {
+            if addr == 0x4000 {
+                self.dev_out = Some(value);
+            } else {
+                self.ram[addr as usize] = value;
+            }
+        }
+    }
+
+    #[test]
+    fn test_bus_trait_routes_io() {
+        let mut bus = DeviceBus {
+            ram: [0; 0x10000],
+            dev_in: 0x5A,
+            dev_out: None,
+        };
+        let mut cpu = CPU::new();
+        cpu.set_pc(0x0600);
+        // LDA $4000 ; STA $4000
+        bus.ram[0x0600] = CPU::INS_LDA_ABS;
+        bus.ram[0x0601] = 0x00;
+        bus.ram[0x0602] = 0x40;
+        bus.ram[0x0603] = CPU::INS_STA_ABS;
+        bus.ram[0x0604] = 0x00;
+        bus.ram[0x0605] = 0x40;
+        // La lectura en 0x4000 devuelve el valor del dispositivo.
+        cpu.step(&mut bus);
+        assert_eq!(cpu.get_a(), 0x5A);
+        // La escritura en 0x4000 se encamina al dispositivo, no a la RAM.
+        cpu.step(&mut bus);
+        assert_eq!(bus.dev_out, Some(0x5A));
+    }
+
+    #[test]
+    fn test_cmos_stz() {
+        let mut cpu = CPU::new_cmos();
+        let mut mem = Mem::new();
+        cpu.set_pc(0x0600);
+        mem[0x30] = 0xFF;
+        mem.load(0x0600, &[CPU::INS_STZ_ZP, 0x30]);
+        cpu.step(&mut mem);
+        // STZ escribe cero en la dirección sin tocar el acumulador.
+        assert_eq!(mem[0x30], 0x00);
+    }
+
+    #[test]
+    fn test_cmos_tsb() {
+        let mut cpu = CPU::new_cmos();
+        let mut mem = Mem::new();
+        cpu.set_pc(0x0600);
+        cpu.set_a(0x0F);
+        mem[0x40] = 0xF0;
+        mem.load(0x0600, &[CPU::INS_TSB_ZP, 0x40]);
+        cpu.step(&mut mem);
+        // La bandera Z refleja A & M (0x0F & 0xF0 = 0), y TSB activa los bits de A.
+        assert_eq!(cpu.get_z(), 1);
+        assert_eq!(mem[0x40], 0xFF);
+    }
+
+    #[test]
+    fn test_cmos_lda_indirect_zp() {
+        let mut cpu = CPU::new_cmos();
+        let mut mem = Mem::new();
+        cpu.set_pc(0x0600);
+        // El operando 0x10 apunta a un puntero little-endian en página cero -> 0x8000.
+        mem[0x10] = 0x00;
+        mem[0x11] = 0x80;
+        mem[0x8000] = 0x42;
+        mem.load(0x0600, &[CPU::INS_LDA_IND_ZP, 0x10]);
+        cpu.step(&mut mem);
+        assert_eq!(cpu.get_a(), 0x42);
+    }
+
+    #[test]
+    fn test_nmos_rejects_cmos_opcode() {
+        // Un 6502 NMOS no decodifica las extensiones del 65C02: cada opcode
+        // exclusivo del CMOS cae en la rama de "no manejado" y execute lo
+        // devuelve al llamador sin haber tocado la memoria.
+        for opcode in [
+            CPU::INS_STZ_ZP,
+            CPU::INS_TSB_ZP,
+            CPU::INS_TRB_ZP,
+            CPU::INS_LDA_IND_ZP,
+        ] {
+            let mut cpu = CPU::new();
+            let mut mem = Mem::new();
+            cpu.set_pc(0x0600);
+            mem[0x30] = 0xAB;
+            mem.load(0x0600, &[opcode, 0x30]);
+            assert_eq!(cpu.execute(10, &mut mem), Some(opcode));
+            assert_eq!(mem[0x30], 0xAB);
+        }
+    }
+
+    #[test]
+    fn test_run_test_success_and_failure() {
+        // Pequeña ROM de conformidad: calcula un valor, lo guarda y cae en una
+        // trampa de bucle cerrado (BRA a sí mismo) en 0x0605, imitando cómo las
+        // ROMs reales señalan el final de su ejecución.
+        //
+        //   0x0600  LDA #$40
+        //   0x0602  INC A        ; A = 0x41
+        //   0x0603  STA $20
+        //   0x0605  BRA $0605    ; trampa de éxito
+        let rom = [
+            CPU::INS_LDA_IM,
+            0x40,
+            CPU::INS_INC_A,
+            CPU::INS_STA_ZP,
+            0x20,
+            CPU::INS_BRA,
+            0xFE,
+        ];
+
+        let mut cpu = CPU::new_cmos();
+        let mut mem = Mem::new();
+        cpu.set_pc(0x0600);
+        mem.load(0x0600, &rom);
+        // La trampa está en su dirección de éxito: la ROM pasa y dejó su huella.
+        assert_eq!(cpu.run_test(&mut mem, 0x0605, 1000), TestOutcome::Success);
+        assert_eq!(cpu.get_a(), 0x41);
+        assert_eq!(mem[0x20], 0x41);
+
+        let mut cpu = CPU::new_cmos();
+        cpu.set_pc(0x0600);
+        // Con otra dirección de éxito, la misma trampa se reporta como fallo,
+        // devolviendo la dirección donde la ejecución se detuvo.
+        assert_eq!(
+            cpu.run_test(&mut mem, 0x0700, 1000),
+            TestOutcome::Failure(0x0605)
+        );
+    }
+
     #[test]
     fn test_index_and_index_mut() {
         let mut mem = Mem::new();